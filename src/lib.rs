@@ -1,21 +1,89 @@
-use anyhow::anyhow;
 use pest::Parser;
 use pest::iterators::Pair;
+use pest::pratt_parser::{Assoc, Op, PrattParser};
 use pest_derive::Parser;
+use std::collections::HashMap;
+use std::fmt;
 use std::fs::OpenOptions;
 use std::io::Write;
+use std::sync::OnceLock;
+
+/// Errors arising from evaluating a well-formed `Expr` against real numbers:
+/// division by zero, functions evaluated outside their domain, and similar.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MathError {
+    /// Division (or `mod`) by zero.
+    DivideByZero,
+    /// `func` is not defined at `arg` (e.g. `ln` of a non-positive number).
+    DomainError { func: String, arg: f64 },
+    /// `root(value, 0)`: a zeroth root is undefined.
+    ZeroRootDegree,
+    /// `log(value, base)` with a non-positive base or a base of 1.
+    InvalidLogBase(f64),
+    /// Evaluating an `Expr::Var` whose name has no binding in the environment.
+    UnboundVariable(String),
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MathError::DivideByZero => write!(f, "division by zero"),
+            MathError::DomainError { func, arg } => {
+                write!(f, "{} is not defined at {}", func, arg)
+            }
+            MathError::ZeroRootDegree => write!(f, "root degree cannot be zero"),
+            MathError::InvalidLogBase(base) => write!(f, "invalid log base: {}", base),
+            MathError::UnboundVariable(name) => write!(f, "unbound variable '{}'", name),
+        }
+    }
+}
+
+impl std::error::Error for MathError {}
+
+/// Top-level error type for this crate: the input could not be parsed
+/// (`Syntax`), it parsed but evaluation failed (`Math`), or writing the
+/// result failed (`Io`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParserError {
+    /// The input did not match the grammar, or the parse tree was malformed.
+    Syntax(String),
+    /// Parsing succeeded but evaluating the resulting `Expr` failed.
+    Math(MathError),
+    /// An I/O error occurred while writing results (e.g. to `res.txt`).
+    Io(String),
+}
+
+impl fmt::Display for ParserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParserError::Syntax(msg) => write!(f, "syntax error: {}", msg),
+            ParserError::Math(err) => write!(f, "evaluation error: {}", err),
+            ParserError::Io(msg) => write!(f, "I/O error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ParserError {}
+
+impl From<MathError> for ParserError {
+    fn from(err: MathError) -> Self {
+        ParserError::Math(err)
+    }
+}
 
 // Parser struct is generated from grammar.pest file
 #[derive(Parser)]
 #[grammar = "grammar.pest"]
 pub struct Grammar;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 /// The enum represents numbers, binary operations (add, sub, mul, div, pow, root, log),
 /// and unary functions (sin, cos, tan, exp, ln).
 pub enum Expr {
     /// A numeric literal.
     Num(f64),
+    /// A variable reference, bound to a value by the caller at evaluation time.
+    Var(String),
     /// Addition: left + right
     Add(Box<Expr>, Box<Expr>),
     /// Subtraction: left - right
@@ -40,26 +108,91 @@ pub enum Expr {
     Root(Box<Expr>, Box<Expr>),
     /// Logarithm with custom base: log(value, base)
     Log(Box<Expr>, Box<Expr>),
+    /// Floating-point remainder: left mod right
+    Mod(Box<Expr>, Box<Expr>),
+    /// Bitwise AND, requires both operands to be integral: left & right
+    BitAnd(Box<Expr>, Box<Expr>),
+    /// Bitwise OR, requires both operands to be integral: left | right
+    BitOr(Box<Expr>, Box<Expr>),
+    /// Bitwise XOR, requires both operands to be integral: left ^^ right
+    BitXor(Box<Expr>, Box<Expr>),
+}
+
+impl std::str::FromStr for Expr {
+    type Err = ParserError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_expression(s)
+    }
+}
+
+impl fmt::Display for Expr {
+    /// Renders `self` back to canonical parenthesized source, e.g.
+    /// `((1 + 2) * (3 + 4))`. The result always reparses via `FromStr` to an
+    /// equivalent `Expr`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use Expr::*;
+        match self {
+            Num(n) => write!(f, "{}", n),
+            Var(name) => write!(f, "{}", name),
+            Add(l, r) => write!(f, "({} + {})", l, r),
+            Sub(l, r) => write!(f, "({} - {})", l, r),
+            Mul(l, r) => write!(f, "({} * {})", l, r),
+            Div(l, r) => write!(f, "({} / {})", l, r),
+            Sin(x) => write!(f, "sin({})", x),
+            Cos(x) => write!(f, "cos({})", x),
+            Tan(x) => write!(f, "tan({})", x),
+            Exp(x) => write!(f, "exp({})", x),
+            Ln(x) => write!(f, "ln({})", x),
+            Pow(l, r) => write!(f, "({} ^ {})", l, r),
+            Root(value, degree) => write!(f, "root({},{})", value, degree),
+            Log(value, base) => write!(f, "log({},{})", value, base),
+            Mod(l, r) => write!(f, "({} mod {})", l, r),
+            BitAnd(l, r) => write!(f, "({} & {})", l, r),
+            BitOr(l, r) => write!(f, "({} | {})", l, r),
+            BitXor(l, r) => write!(f, "({} ^^ {})", l, r),
+        }
+    }
+}
+
+/// Returns the precedence climbing table for the natural-infix grammar
+/// (`infix_expr`), built once and reused across calls.
+///
+/// Precedence, lowest to highest: bitwise OR/XOR, bitwise AND, additive,
+/// multiplicative/`mod`, then power. All tiers are left-associative except
+/// power, which is right-associative.
+fn pratt_parser() -> &'static PrattParser<Rule> {
+    static PRATT: OnceLock<PrattParser<Rule>> = OnceLock::new();
+    PRATT.get_or_init(|| {
+        PrattParser::new()
+            .op(Op::infix(Rule::bit_or, Assoc::Left) | Op::infix(Rule::bit_xor, Assoc::Left))
+            .op(Op::infix(Rule::bit_and, Assoc::Left))
+            .op(Op::infix(Rule::add, Assoc::Left) | Op::infix(Rule::subtract, Assoc::Left))
+            .op(Op::infix(Rule::multiply_op, Assoc::Left)
+                | Op::infix(Rule::divide_op, Assoc::Left)
+                | Op::infix(Rule::modulo, Assoc::Left))
+            .op(Op::infix(Rule::power, Assoc::Right))
+    })
 }
 
 /// This function walks the parse tree produced by Pest and converts rules
 /// into the corresponding `Expr` variants. It returns an error for unexpected
 /// or malformed input.
-fn build_expr(pair: Pair<Rule>) -> anyhow::Result<Expr> {
+fn build_expr(pair: Pair<Rule>) -> Result<Expr, ParserError> {
     match pair.as_rule() {
         Rule::input | Rule::expression => {
             let mut inner = pair.into_inner();
             if let Some(p) = inner.next() {
                 build_expr(p)
             } else {
-                Err(anyhow!("Empty expression"))
+                Err(ParserError::Syntax("empty expression".to_string()))
             }
         }
         Rule::num => {
             let s = pair.as_str();
-            s.parse::<f64>()
-                .map(Expr::Num)
-                .map_err(|e| anyhow!("Failed to parse number '{}': {}", s, e))
+            s.parse::<f64>().map(Expr::Num).map_err(|e| {
+                ParserError::Syntax(format!("failed to parse number '{}': {}", s, e))
+            })
         }
         Rule::plus
         | Rule::minus
@@ -69,8 +202,12 @@ fn build_expr(pair: Pair<Rule>) -> anyhow::Result<Expr> {
         | Rule::log
         | Rule::root => {
             let mut inner = pair.clone().into_inner();
-            let left = inner.next().ok_or_else(|| anyhow!("Missing left"))?;
-            let right = inner.next().ok_or_else(|| anyhow!("Missing right"))?;
+            let left = inner
+                .next()
+                .ok_or_else(|| ParserError::Syntax("missing left operand".to_string()))?;
+            let right = inner
+                .next()
+                .ok_or_else(|| ParserError::Syntax("missing right operand".to_string()))?;
             let l = build_expr(left)?;
             let r = build_expr(right)?;
             match pair.as_rule() {
@@ -86,7 +223,9 @@ fn build_expr(pair: Pair<Rule>) -> anyhow::Result<Expr> {
         }
         Rule::sin | Rule::cos | Rule::tan | Rule::exp | Rule::ln => {
             let mut inner = pair.clone().into_inner();
-            let v = inner.next().ok_or_else(|| anyhow!("Missing argument"))?;
+            let v = inner
+                .next()
+                .ok_or_else(|| ParserError::Syntax("missing argument".to_string()))?;
             let expr = build_expr(v)?;
             match pair.as_rule() {
                 Rule::sin => Ok(Expr::Sin(Box::new(expr))),
@@ -97,86 +236,220 @@ fn build_expr(pair: Pair<Rule>) -> anyhow::Result<Expr> {
                 _ => unreachable!(),
             }
         }
-        _ => Err(anyhow!("Unexpected rule: {:?}", pair.as_rule())),
+        Rule::var => Ok(Expr::Var(pair.as_str().to_string())),
+        Rule::hex_num | Rule::bin_num | Rule::oct_num => {
+            let s = pair.as_str();
+            let (radix, digits) = match pair.as_rule() {
+                Rule::hex_num => (16, &s[2..]),
+                Rule::bin_num => (2, &s[2..]),
+                Rule::oct_num => (8, &s[2..]),
+                _ => unreachable!(),
+            };
+            i64::from_str_radix(digits, radix)
+                .map(|n| Expr::Num(n as f64))
+                .map_err(|e| ParserError::Syntax(format!("failed to parse literal '{}': {}", s, e)))
+        }
+        Rule::infix_expr => pratt_parser()
+            .map_primary(build_expr)
+            .map_infix(|lhs, op, rhs| {
+                let l = lhs?;
+                let r = rhs?;
+                match op.as_rule() {
+                    Rule::add => Ok(Expr::Add(Box::new(l), Box::new(r))),
+                    Rule::subtract => Ok(Expr::Sub(Box::new(l), Box::new(r))),
+                    Rule::multiply_op => Ok(Expr::Mul(Box::new(l), Box::new(r))),
+                    Rule::divide_op => Ok(Expr::Div(Box::new(l), Box::new(r))),
+                    Rule::modulo => Ok(Expr::Mod(Box::new(l), Box::new(r))),
+                    Rule::bit_and => Ok(Expr::BitAnd(Box::new(l), Box::new(r))),
+                    Rule::bit_or => Ok(Expr::BitOr(Box::new(l), Box::new(r))),
+                    Rule::bit_xor => Ok(Expr::BitXor(Box::new(l), Box::new(r))),
+                    Rule::power => Ok(Expr::Pow(Box::new(l), Box::new(r))),
+                    _ => unreachable!(),
+                }
+            })
+            .parse(pair.into_inner()),
+        _ => Err(ParserError::Syntax(format!(
+            "unexpected rule: {:?}",
+            pair.as_rule()
+        ))),
     }
 }
 
 /// This performs runtime checks (division by zero, invalid arguments for ln/log/root)
 /// and returns descriptive errors via `anyhow` when evaluation cannot proceed.
-fn eval(e: &Expr) -> anyhow::Result<f64> {
+///
+/// `vars` supplies the value bound to each `Expr::Var` encountered; evaluating
+/// a name absent from `vars` is an error.
+fn eval(e: &Expr, vars: &HashMap<String, f64>) -> Result<f64, ParserError> {
     use Expr::*;
     match e {
         Num(n) => Ok(*n),
-        Add(a, b) => Ok(eval(a)? + eval(b)?),
-        Sub(a, b) => Ok(eval(a)? - eval(b)?),
-        Mul(a, b) => Ok(eval(a)? * eval(b)?),
+        Var(name) => vars
+            .get(name)
+            .copied()
+            .ok_or_else(|| MathError::UnboundVariable(name.clone()).into()),
+        Add(a, b) => Ok(eval(a, vars)? + eval(b, vars)?),
+        Sub(a, b) => Ok(eval(a, vars)? - eval(b, vars)?),
+        Mul(a, b) => Ok(eval(a, vars)? * eval(b, vars)?),
         Div(a, b) => {
-            let rv = eval(b)?;
+            let rv = eval(b, vars)?;
             if rv == 0.0 {
-                Err(anyhow!("Division by zero"))
+                Err(MathError::DivideByZero.into())
             } else {
-                Ok(eval(a)? / rv)
+                Ok(eval(a, vars)? / rv)
             }
         }
-        Sin(x) => Ok(eval(x)?.sin()),
-        Cos(x) => Ok(eval(x)?.cos()),
-        Tan(x) => Ok(eval(x)?.tan()),
-        Exp(x) => Ok(eval(x)?.exp()),
-        Pow(a, b) => Ok(eval(a)?.powf(eval(b)?)),
+        Sin(x) => Ok(eval(x, vars)?.sin()),
+        Cos(x) => Ok(eval(x, vars)?.cos()),
+        Tan(x) => Ok(eval(x, vars)?.tan()),
+        Exp(x) => Ok(eval(x, vars)?.exp()),
+        Pow(a, b) => Ok(eval(a, vars)?.powf(eval(b, vars)?)),
         Log(value, base) => {
-            let v = eval(value)?;
-            let b = eval(base)?;
-            if v <= 0.0 || b <= 0.0 || b == 1.0 {
-                Err(anyhow!(
-                    "Invalid arguments for log(value, base): value={} base={}",
-                    v,
-                    b
-                ))
+            let v = eval(value, vars)?;
+            let b = eval(base, vars)?;
+            if v <= 0.0 {
+                Err(MathError::DomainError {
+                    func: "log".to_string(),
+                    arg: v,
+                }
+                .into())
+            } else if b <= 0.0 || b == 1.0 {
+                Err(MathError::InvalidLogBase(b).into())
             } else {
                 Ok(v.ln() / b.ln())
             }
         }
         Ln(x) => {
-            let v = eval(x)?;
+            let v = eval(x, vars)?;
             if v <= 0.0 {
-                Err(anyhow!("Invalid argument for ln: {}", v))
+                Err(MathError::DomainError {
+                    func: "ln".to_string(),
+                    arg: v,
+                }
+                .into())
             } else {
                 Ok(v.ln())
             }
         }
         Root(value, degree) => {
-            let deg = eval(degree)?;
+            let deg = eval(degree, vars)?;
             if deg == 0.0 {
-                Err(anyhow!("Root degree cannot be zero"))
+                Err(MathError::ZeroRootDegree.into())
+            } else {
+                Ok(eval(value, vars)?.powf(1.0 / deg))
+            }
+        }
+        Mod(a, b) => {
+            let rv = eval(b, vars)?;
+            if rv == 0.0 {
+                Err(MathError::DivideByZero.into())
             } else {
-                Ok(eval(value)?.powf(1.0 / deg))
+                Ok(eval(a, vars)? % rv)
             }
         }
+        BitAnd(a, b) => Ok((int_operand(eval(a, vars)?)? & int_operand(eval(b, vars)?)?) as f64),
+        BitOr(a, b) => Ok((int_operand(eval(a, vars)?)? | int_operand(eval(b, vars)?)?) as f64),
+        BitXor(a, b) => Ok((int_operand(eval(a, vars)?)? ^ int_operand(eval(b, vars)?)?) as f64),
+    }
+}
+
+/// Casts `v` to `i64` for a bitwise operator, rejecting fractional values.
+fn int_operand(v: f64) -> Result<i64, ParserError> {
+    if v.fract() != 0.0 {
+        Err(MathError::DomainError {
+            func: "bitwise operator".to_string(),
+            arg: v,
+        }
+        .into())
+    } else {
+        Ok(v as i64)
     }
 }
 
 /// Parse the input string into an `Expr` AST.
 ///
 /// Returns a descriptive error if parsing fails.
-pub fn parse_expression(input: &str) -> anyhow::Result<Expr> {
-    let pair = Grammar::parse(Rule::input, input)?
+pub fn parse_expression(input: &str) -> Result<Expr, ParserError> {
+    let pair = Grammar::parse(Rule::input, input)
+        .map_err(|e| ParserError::Syntax(e.to_string()))?
         .next()
-        .ok_or_else(|| anyhow!("Failed to parse input"))?;
+        .ok_or_else(|| ParserError::Syntax("failed to parse input".to_string()))?;
     build_expr(pair)
 }
 
 /// Evaluate a previously parsed `Expr`.
 ///
 /// Returns the numeric result or an error if evaluation fails.
-pub fn eval_expr(expr: &Expr) -> anyhow::Result<f64> {
-    eval(expr)
+pub fn eval_expr(expr: &Expr) -> Result<f64, ParserError> {
+    eval(expr, &HashMap::new())
+}
+
+/// Evaluate a previously parsed `Expr`, binding `Expr::Var` names to values from `vars`.
+///
+/// Returns the numeric result, or an error if evaluation fails or the
+/// expression references a name not present in `vars`.
+pub fn eval_with(expr: &Expr, vars: &HashMap<String, f64>) -> Result<f64, ParserError> {
+    eval(expr, vars)
+}
+
+/// Parse `input` once and return a closure that evaluates it against a map of
+/// variable bindings, for sampling the same expression at many points (e.g.
+/// tabulating `sin(x)/x` over a range) without re-parsing.
+pub fn compile(
+    input: &str,
+) -> Result<impl Fn(&HashMap<String, f64>) -> Result<f64, ParserError>, ParserError> {
+    let expr = parse_expression(input)?;
+    Ok(move |vars: &HashMap<String, f64>| eval_with(&expr, vars))
+}
+
+/// Variable bindings shared across the lines of a multi-line script.
+pub type Env = HashMap<String, f64>;
+
+/// Evaluate one line of a multi-line script against `env`.
+///
+/// A `let name = expr` line evaluates `expr` against the current bindings,
+/// stores the result under `name` in `env`, and returns it. Any other line
+/// is evaluated as a plain expression against `env` without modifying it.
+/// Either way, later lines fed through the same `env` can see bindings made
+/// by earlier ones.
+pub fn eval_line(line: &str, env: &mut Env) -> Result<f64, ParserError> {
+    let pair = Grammar::parse(Rule::input, line)
+        .map_err(|e| ParserError::Syntax(e.to_string()))?
+        .next()
+        .ok_or_else(|| ParserError::Syntax("failed to parse input".to_string()))?;
+    let stmt = pair
+        .into_inner()
+        .next()
+        .ok_or_else(|| ParserError::Syntax("empty line".to_string()))?;
+
+    match stmt.as_rule() {
+        Rule::let_stmt => {
+            let mut inner = stmt.into_inner();
+            let name = inner
+                .next()
+                .ok_or_else(|| ParserError::Syntax("missing binding name".to_string()))?
+                .as_str()
+                .to_string();
+            let expr_pair = inner
+                .next()
+                .ok_or_else(|| ParserError::Syntax("missing bound expression".to_string()))?;
+            let expr = build_expr(expr_pair)?;
+            let value = eval(&expr, env)?;
+            env.insert(name, value);
+            Ok(value)
+        }
+        _ => {
+            let expr = build_expr(stmt)?;
+            eval(&expr, env)
+        }
+    }
 }
 
 /// Convenience: parse the input, evaluate it, and append the result to `res.txt`.
 ///
 /// The function returns the computed value or an error. The output file is opened
 /// in append mode and created if it does not exist.
-pub fn parse_and_eval(input: &str) -> anyhow::Result<f64> {
+pub fn parse_and_eval(input: &str) -> Result<f64, ParserError> {
     let e = parse_expression(input)?;
     let res = eval_expr(&e)?;
 
@@ -184,9 +457,9 @@ pub fn parse_and_eval(input: &str) -> anyhow::Result<f64> {
         .create(true)
         .append(true)
         .open("res.txt")
-        .map_err(|e| anyhow!("Failed to open res.txt: {}", e))?;
+        .map_err(|e| ParserError::Io(format!("failed to open res.txt: {}", e)))?;
     writeln!(file, "({}) = {}", input.trim(), res)
-        .map_err(|e| anyhow!("Failed to write to res.txt: {}", e))?;
+        .map_err(|e| ParserError::Io(format!("failed to write to res.txt: {}", e)))?;
 
     Ok(res)
 }
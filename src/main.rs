@@ -1,5 +1,7 @@
 use clap::{Arg, Command, builder::PathBufValueParser};
-use math_expression_parser::parse_and_eval;
+use math_expression_parser::{Env, eval_line};
+use std::fs::OpenOptions;
+use std::io::Write;
 use std::path::PathBuf;
 
 fn main() -> anyhow::Result<()> {
@@ -32,8 +34,17 @@ fn main() -> anyhow::Result<()> {
     let file_path: &PathBuf = matches.get_one("file").expect("No file path provided");
     let input = std::fs::read_to_string(file_path)?;
     let lines: Vec<&str> = input.lines().collect();
-    for input in lines {
-        parse_and_eval(input)?;
+
+    // One environment shared across every line, so a `let` binding on an
+    // earlier line is visible to expressions on later lines.
+    let mut env = Env::new();
+    let mut res_file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open("res.txt")?;
+    for line in lines {
+        let value = eval_line(line, &mut env)?;
+        writeln!(res_file, "({}) = {}", line.trim(), value)?;
     }
     println!("Successfully evaluated expressions.");
     println!("Results have been written to res.txt");
@@ -1,4 +1,8 @@
-use math_expression_parser::{Expr, eval_expr, parse_and_eval, parse_expression};
+use math_expression_parser::{
+    Env, Expr, MathError, ParserError, compile, eval_expr, eval_line, eval_with, parse_and_eval,
+    parse_expression,
+};
+use std::collections::HashMap;
 
 fn approx_eq(a: f64, b: f64, eps: f64) -> bool {
     (a - b).abs() <= eps
@@ -140,6 +144,159 @@ fn nested_expression_eval() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn infix_precedence_and_associativity() -> anyhow::Result<()> {
+    // 3 + 4 * 5 - 2 ^ 3 / sin(0)... sin(0) is 0, so division would blow up;
+    // use cos(0) = 1 instead so the whole expression is well defined.
+    let v = parse_and_eval("3 + 4 * 5 - 2 ^ 3 / cos(0)")?;
+    assert!(approx_eq(v, 3.0 + 4.0 * 5.0 - 2f64.powf(3.0) / 1.0, 1e-12));
+    Ok(())
+}
+
+#[test]
+fn infix_right_associative_power() -> anyhow::Result<()> {
+    // 2 ^ 3 ^ 2 must read as 2 ^ (3 ^ 2) = 512, not (2 ^ 3) ^ 2 = 64.
+    let v = parse_and_eval("2 ^ 3 ^ 2")?;
+    assert!(approx_eq(v, 512.0, 1e-9));
+    Ok(())
+}
+
+#[test]
+fn infix_and_parenthesized_forms_coexist() -> anyhow::Result<()> {
+    let old = parse_and_eval("(12+34)")?;
+    let new = parse_and_eval("12 + 34")?;
+    assert_eq!(old, new);
+    Ok(())
+}
+
+#[test]
+fn eval_with_binds_variables() -> anyhow::Result<()> {
+    let e = parse_expression("x * 2")?;
+    let mut vars = HashMap::new();
+    vars.insert("x".to_string(), 21.0);
+    let v = eval_with(&e, &vars)?;
+    assert_eq!(v, 42.0);
+    Ok(())
+}
+
+#[test]
+fn eval_with_unbound_variable_errors() {
+    let e = parse_expression("x").unwrap();
+    assert!(eval_with(&e, &HashMap::new()).is_err());
+}
+
+#[test]
+fn compile_reuses_parsed_expression_across_points() -> anyhow::Result<()> {
+    let f = compile("sin(x)/x")?;
+    let mut vars = HashMap::new();
+    for &x in &[0.5f64, 1.0, 2.0] {
+        vars.insert("x".to_string(), x);
+        let expected = x.sin() / x;
+        assert!(approx_eq(f(&vars)?, expected, 1e-12));
+    }
+    Ok(())
+}
+
+#[test]
+fn divide_by_zero_is_a_math_error() {
+    let err = parse_and_eval("(1/0)").unwrap_err();
+    assert_eq!(err, ParserError::Math(MathError::DivideByZero));
+}
+
+#[test]
+fn ln_domain_error_names_the_function_and_argument() {
+    let err = parse_and_eval("ln(-1)").unwrap_err();
+    assert_eq!(
+        err,
+        ParserError::Math(MathError::DomainError {
+            func: "ln".to_string(),
+            arg: -1.0,
+        })
+    );
+}
+
+#[test]
+fn malformed_input_is_a_syntax_error() {
+    let err = parse_expression("(1+)").unwrap_err();
+    assert!(matches!(err, ParserError::Syntax(_)));
+}
+
+#[test]
+fn hex_bin_and_octal_literals() -> anyhow::Result<()> {
+    assert_eq!(parse_and_eval("0xFF")?, 255.0);
+    assert_eq!(parse_and_eval("0b1010")?, 10.0);
+    assert_eq!(parse_and_eval("0o17")?, 15.0);
+    Ok(())
+}
+
+#[test]
+fn modulo_and_bitwise_operators() -> anyhow::Result<()> {
+    assert_eq!(parse_and_eval("10 mod 3")?, 1.0);
+    assert_eq!(parse_and_eval("0xFF & 0x0F")?, 15.0);
+    assert_eq!(parse_and_eval("0x0F | 0xF0")?, 255.0);
+    assert_eq!(parse_and_eval("5 ^^ 3")?, 6.0);
+    Ok(())
+}
+
+#[test]
+fn bitwise_operator_rejects_fractional_operand() {
+    let err = parse_and_eval("1.5 & 1").unwrap_err();
+    assert!(matches!(err, ParserError::Math(MathError::DomainError { .. })));
+}
+
+#[test]
+fn let_binding_is_visible_to_later_lines() -> anyhow::Result<()> {
+    let mut env = Env::new();
+    assert_eq!(eval_line("let r = 5", &mut env)?, 5.0);
+    assert_eq!(eval_line("pow(r,2)", &mut env)?, 25.0);
+    assert_eq!(eval_line("(r*2)", &mut env)?, 10.0);
+    Ok(())
+}
+
+#[test]
+fn eval_line_plain_expression_does_not_mutate_env() -> anyhow::Result<()> {
+    let mut env = Env::new();
+    eval_line("let a = 1", &mut env)?;
+    eval_line("a + 1", &mut env)?;
+    assert_eq!(env.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn display_renders_canonical_parenthesized_form() -> anyhow::Result<()> {
+    let e = parse_expression("((1+2)*(3+4))")?;
+    assert_eq!(e.to_string(), "((1 + 2) * (3 + 4))");
+    Ok(())
+}
+
+#[test]
+fn display_then_parse_roundtrips_to_an_equivalent_tree() -> anyhow::Result<()> {
+    for input in [
+        "(12+34)",
+        "(5-3)",
+        "(2*3)",
+        "(10/2)",
+        "sin(0)",
+        "cos(0)",
+        "tan(0)",
+        "exp(1)",
+        "pow(2,3)",
+        "root(27,3)",
+        "log(8,2)",
+        "ln(2.718281828459045)",
+        "((1+2)*(3+4))",
+        "3 + 4 * 5 - 2 ^ 3 / cos(0)",
+        "10 mod 3",
+        "0xFF & 0x0F",
+        "5 ^^ 3",
+    ] {
+        let original: Expr = input.parse()?;
+        let reparsed: Expr = original.to_string().parse()?;
+        assert_eq!(original, reparsed, "roundtrip mismatch for {}", input);
+    }
+    Ok(())
+}
+
 #[test]
 fn invalid_ln_and_log_errors() {
     // ln of non-positive is an error